@@ -0,0 +1,265 @@
+use lazy_regex::regex::Regex;
+
+use crate::vrc_log_reader::{parse_timestamp, FoundSeek, FoundUrl};
+
+/// Which kind of event a `DetectionPattern` produces when it matches a line.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum PatternKind {
+    Url,
+    Seek,
+}
+
+/// One named, user-extensible way to recognize a URL or seek event in a VRChat log line.
+///
+/// The built-in patterns cover VRChat's `[Video Playback] Attempting to resolve URL` line and
+/// ProTV's `TVManager` seek lines. Other line shapes (raw AVProVideo lines, other sync systems)
+/// aren't matched out of the box; people running those can add their own via config without
+/// recompiling, as long as they can express the line shape as a regex and point us at the right
+/// groups.
+pub(crate) struct DetectionPattern {
+    pub(crate) name: String,
+    pub(crate) kind: PatternKind,
+    pub(crate) regex: Regex,
+    /// 1-based capture group holding the `2024.04.22 17:55:53`-style timestamp.
+    pub(crate) timestamp_group: usize,
+    /// 1-based capture group holding the URL. Required for `PatternKind::Url` patterns.
+    pub(crate) url_group: Option<usize>,
+    /// 1-based capture group holding the seek offset. Required for `PatternKind::Seek` patterns.
+    pub(crate) offset_group: Option<usize>,
+    /// 1-based capture group that's `"Paused drift threshold exceeded"` when the event means
+    /// playback is paused, and anything else otherwise. Optional for `PatternKind::Seek`
+    /// patterns; if absent, events from this pattern are never treated as a pause.
+    pub(crate) paused_group: Option<usize>,
+}
+
+impl DetectionPattern {
+    fn try_match_url(&self, line: &str, line_number: u64) -> Option<FoundUrl> {
+        if self.kind != PatternKind::Url {
+            return None;
+        }
+        let captures = self.regex.captures(line)?;
+        let timestamp = captures.get(self.timestamp_group)?.as_str();
+        let url = captures.get(self.url_group?)?.as_str();
+        Some(FoundUrl {
+            timestamp: parse_timestamp(timestamp),
+            url: url.to_string(),
+            found_url_on_line: line_number,
+        })
+    }
+
+    fn try_match_seek(&self, line: &str) -> Option<FoundSeek> {
+        if self.kind != PatternKind::Seek {
+            return None;
+        }
+        let captures = self.regex.captures(line)?;
+        let timestamp = captures.get(self.timestamp_group)?.as_str();
+        let seek_offset = captures
+            .get(self.offset_group?)?
+            .as_str()
+            .parse::<f64>()
+            .ok()?;
+        let paused = self
+            .paused_group
+            .and_then(|group| captures.get(group))
+            .map(|m| m.as_str() == "Paused drift threshold exceeded")
+            .unwrap_or(false);
+        Some(FoundSeek {
+            timestamp: parse_timestamp(timestamp),
+            seek_offset,
+            paused,
+        })
+    }
+}
+
+/*
+We have several examples of log lines to choose from.
+
+"2024.04.14 23:28:20 Log        -  [AT INFO        TVManager (Theatre 1)] [AVPro1080p_Theatre1] loading URL:
+https://example.net/Media/Movies/spykids3d.mp4"
+Initially, I had this one, but obviously this doesn't work if I'm not in the theatre.
+
+"2024.04.14 21:25:36 Log        -  [AVProVideo] Opening http://example.com/mystream/stream.m3u8 (offset 0)
+with API MediaFoundation"
+AVProVideo might be a good option, but it likely doesn't capture usage of the Unity player, which iirc also needs
+fixing.
+
+"2024.04.14 21:25:36 Log        -  [Video Playback] URL 'http://example.com/mystream/index.m3u8' resolved to
+'http://example.com/mystream/stream.m3u8'"
+This one is more general, and might work everywhere, but it is 2 seconds delayed. If I don't need the resolution,
+I'd prefer the earlier the better.
+
+"2024.04.14 21:25:34 Log        -  [Video Playback] Attempting to resolve URL
+'http://example.com/mystream/index.m3u8'"
+I'll go with this one for now, as it's the earliest and the easiest.
+*/
+fn default_url_pattern() -> DetectionPattern {
+    DetectionPattern {
+        name: "video_playback_attempting_to_resolve".to_string(),
+        kind: PatternKind::Url,
+        regex: Regex::new(
+            r"^([0-9.: ]+) Log +- +\[Video Playback\] Attempting to resolve URL '(https?://\S+)'",
+        )
+        .expect("Built-in URL regex is valid."),
+        timestamp_group: 1,
+        url_group: Some(2),
+        offset_group: None,
+        paused_group: None,
+    }
+}
+
+// this is specifically for ProTV.
+// 2024.04.22 17:55:53 Log        -  [AT INFO    	TVManager (Theatre 1 TVManager)] Sync enforcement. Updating to 116.47
+// 2024.05.09 19:11:19 Log        -  [AT DEBUG 	TVManager (Theatre 1 TVManager)] Paused drift threshold exceeded. Updating to 64.8041
+fn default_seek_pattern() -> DetectionPattern {
+    DetectionPattern {
+        name: "protv_sync_enforcement".to_string(),
+        kind: PatternKind::Seek,
+        regex: Regex::new(
+            r"^([0-9.: ]+) Log +- +\[AT (INFO|DEBUG)[ \t]+TVManager \(.*\)\] (Sync enforcement|Paused drift threshold exceeded). Updating to ([0-9.]+)$",
+        )
+        .expect("Built-in seek regex is valid."),
+        timestamp_group: 1,
+        url_group: None,
+        offset_group: Some(4),
+        paused_group: Some(3),
+    }
+}
+
+/// An ordered list of detection patterns. Earlier-registered patterns win when more than one
+/// matches the same line, so the "earliest is best" preference documented above is honored
+/// even as more patterns are registered.
+pub(crate) struct PatternRegistry {
+    patterns: Vec<DetectionPattern>,
+}
+
+impl PatternRegistry {
+    /// The registry used when no config-supplied patterns are present: ProTV's seek line and
+    /// the earliest of the four URL line shapes we know about.
+    pub(crate) fn defaults() -> Self {
+        Self {
+            patterns: vec![default_url_pattern(), default_seek_pattern()],
+        }
+    }
+
+    pub(crate) fn with_extra_patterns(mut self, extra: Vec<DetectionPattern>) -> Self {
+        self.patterns.extend(extra);
+        self
+    }
+
+    pub(crate) fn try_match_url_line(&self, line: &str, line_number: u64) -> Option<FoundUrl> {
+        self.patterns
+            .iter()
+            .find_map(|pattern| pattern.try_match_url(line, line_number))
+    }
+
+    pub(crate) fn try_match_seek_line(&self, line: &str) -> Option<FoundSeek> {
+        self.patterns
+            .iter()
+            .find_map(|pattern| pattern.try_match_seek(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(name: &str, regex: &str, url_group: usize) -> DetectionPattern {
+        DetectionPattern {
+            name: name.to_string(),
+            kind: PatternKind::Url,
+            regex: Regex::new(regex).expect("test regex is valid"),
+            timestamp_group: 1,
+            url_group: Some(url_group),
+            offset_group: None,
+            paused_group: None,
+        }
+    }
+
+    #[test]
+    fn default_url_pattern_matches_video_playback_line() {
+        let registry = PatternRegistry::defaults();
+        let found = registry
+            .try_match_url_line(
+                "2024.04.14 21:25:34 Log        -  [Video Playback] Attempting to resolve URL 'http://example.com/mystream/index.m3u8'",
+                7,
+            )
+            .expect("should match the built-in pattern");
+        assert_eq!(found.url, "http://example.com/mystream/index.m3u8");
+        assert_eq!(found.found_url_on_line, 7);
+    }
+
+    #[test]
+    fn default_seek_pattern_captures_pause_state() {
+        let registry = PatternRegistry::defaults();
+        let playing = registry
+            .try_match_seek_line(
+                "2024.04.22 17:55:53 Log        -  [AT INFO    \tTVManager (Theatre 1 TVManager)] Sync enforcement. Updating to 116.47",
+            )
+            .expect("should match the sync enforcement line");
+        assert_eq!(playing.seek_offset, 116.47);
+        assert!(!playing.paused);
+
+        let paused = registry
+            .try_match_seek_line(
+                "2024.05.09 19:11:19 Log        -  [AT DEBUG \tTVManager (Theatre 1 TVManager)] Paused drift threshold exceeded. Updating to 64.8041",
+            )
+            .expect("should match the paused drift line");
+        assert_eq!(paused.seek_offset, 64.8041);
+        assert!(paused.paused);
+    }
+
+    #[test]
+    fn custom_pattern_with_reordered_capture_groups_is_respected() {
+        // capture group 1 is the url, group 2 is the timestamp here, the reverse of the
+        // built-in patterns, to make sure we honor *_group rather than assuming an order.
+        let mut pattern = pattern(
+            "reordered",
+            r"^URL=(\S+) AT=([0-9.: ]+)$",
+            1,
+        );
+        pattern.timestamp_group = 2;
+        let registry = PatternRegistry::defaults().with_extra_patterns(vec![pattern]);
+
+        let found = registry
+            .try_match_url_line("URL=http://example.com/stream.m3u8 AT=2024.04.22 17:55:53", 1)
+            .expect("custom pattern should match");
+        assert_eq!(found.url, "http://example.com/stream.m3u8");
+    }
+
+    #[test]
+    fn first_registered_pattern_wins_on_overlap() {
+        // both patterns match the same line, but capture a different URL: the first one
+        // registered should win. Group 1 is a timestamp in both, since `pattern()` always
+        // points `timestamp_group` there.
+        let preferred = pattern(
+            "preferred",
+            r"^([0-9.: ]+) PRIMARY=(\S+) SECONDARY=\S+$",
+            2,
+        );
+        let other = pattern("other", r"^([0-9.: ]+) PRIMARY=\S+ SECONDARY=(\S+)$", 2);
+        let registry = PatternRegistry {
+            patterns: vec![preferred, other],
+        };
+
+        let found = registry
+            .try_match_url_line(
+                "2024.04.22 17:55:53 PRIMARY=http://example.com/a.m3u8 SECONDARY=http://example.com/b.m3u8",
+                1,
+            )
+            .expect("one of the patterns should match");
+        assert_eq!(found.url, "http://example.com/a.m3u8");
+    }
+
+    #[test]
+    fn later_pattern_is_tried_when_earlier_ones_fail_to_match() {
+        let unmatchable = pattern("unmatchable", r"^NEVER_MATCHES$", 1);
+        let fallback = pattern("fallback", r"^([0-9.: ]+) URL=(\S+)$", 2);
+        let registry = PatternRegistry {
+            patterns: vec![unmatchable, fallback],
+        };
+        let found = registry
+            .try_match_url_line("2024.04.22 17:55:53 URL=http://example.com/b.m3u8", 1)
+            .expect("fallback pattern should still match");
+        assert_eq!(found.url, "http://example.com/b.m3u8");
+    }
+}
@@ -0,0 +1,74 @@
+use std::thread;
+
+use chrono::Local;
+
+use crate::{
+    log_debug, log_error,
+    player_sink::PlayerSink,
+    seek_tracker::SeekTracker,
+    stream_resolver::resolve_stream,
+    vrc_log_reader::{FoundSeek, FoundUrl},
+};
+
+/// How far ProTV's reported position may drift from our prediction before we bother
+/// resyncing the player. ProTV logs corrections frequently, so without this most seeks
+/// would just be redundant churn.
+const DEFAULT_SEEK_EPSILON_SECS: f64 = 1.0;
+
+/// Wires `VrcLogWatcher` events to a `PlayerSink`: resolves each new URL through `ffprobe`
+/// before handing it to the player, and uses a `SeekTracker` to turn ProTV's seek events into
+/// absolute, drift-corrected positions, only resyncing the player when the drift is real.
+pub(crate) struct PlayerDriver<S: PlayerSink> {
+    sink: S,
+    seek_tracker: SeekTracker,
+}
+
+impl<S: PlayerSink> PlayerDriver<S> {
+    pub(crate) fn new(sink: S) -> Self {
+        Self {
+            sink,
+            seek_tracker: SeekTracker::new(DEFAULT_SEEK_EPSILON_SECS),
+        }
+    }
+
+    pub(crate) fn on_found_url(&mut self, found_url: &FoundUrl) {
+        // ffprobe is only consulted for its logging value here, not for anything load_url
+        // depends on, but it shells out over the network and can hang on a dead stream. Run
+        // it on its own thread so a stuck probe can't wedge the log-tailing loop this callback
+        // runs on.
+        let url = found_url.url.clone();
+        thread::spawn(move || match resolve_stream(&url) {
+            Ok(resolved) => log_debug!(
+                "ffprobe resolved {} to {} track(s), duration {:?}s",
+                url,
+                resolved.track_count,
+                resolved.duration_secs
+            ),
+            Err(err) => log_error!("ffprobe failed to resolve {}: {:?}", url, err),
+        });
+
+        if let Err(err) = self.sink.load_url(&found_url.url) {
+            log_error!("Failed to load {} into the player: {:?}", found_url.url, err);
+        }
+    }
+
+    pub(crate) fn on_found_seek(&mut self, found_seek: &FoundSeek) {
+        let Some(correction) = self.seek_tracker.observe(found_seek) else {
+            return;
+        };
+
+        let position = if correction.paused {
+            correction.position_secs
+        } else {
+            let elapsed = Local::now()
+                .signed_duration_since(correction.timestamp)
+                .to_std()
+                .unwrap_or_default();
+            correction.position_secs + elapsed.as_secs_f64()
+        };
+
+        if let Err(err) = self.sink.seek_to(position) {
+            log_error!("Failed to seek the player to {}: {:?}", position, err);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use chrono::{DateTime, Local};
+
+use crate::vrc_log_reader::FoundSeek;
+
+struct AcceptedSeek {
+    seek_offset: f64,
+    timestamp: DateTime<Local>,
+    paused: bool,
+}
+
+/// A `FoundSeek` that cleared the drift threshold or changed the paused/playing state, and
+/// should be sent downstream.
+pub(crate) struct SeekCorrection {
+    pub(crate) position_secs: f64,
+    pub(crate) timestamp: DateTime<Local>,
+    pub(crate) paused: bool,
+}
+
+/// Tracks ProTV's reported playback position over time.
+///
+/// ProTV logs `Sync enforcement`/`Paused drift threshold exceeded` lines frequently, often
+/// with identical or near-identical offsets, which would cause redundant correction churn in
+/// any consumer. `SeekTracker` suppresses events that don't represent real drift and
+/// maintains a virtual playback clock so callers can ask where ProTV's position should be
+/// right now without waiting on the next log line.
+pub(crate) struct SeekTracker {
+    epsilon_secs: f64,
+    last_accepted: Option<AcceptedSeek>,
+}
+
+impl SeekTracker {
+    pub(crate) fn new(epsilon_secs: f64) -> Self {
+        Self {
+            epsilon_secs,
+            last_accepted: None,
+        }
+    }
+
+    /// Feeds a new `FoundSeek` to the tracker. Returns `Some` only when the event is
+    /// significant: either the predicted position at the time of this event was off by more
+    /// than `epsilon_secs`, or the paused/playing state changed. Out-of-order timestamps
+    /// (older than the last accepted event) are ignored entirely.
+    pub(crate) fn observe(&mut self, found_seek: &FoundSeek) -> Option<SeekCorrection> {
+        if let Some(last) = &self.last_accepted {
+            if found_seek.timestamp < last.timestamp {
+                return None;
+            }
+        }
+
+        let predicted = self.predicted_position_at(found_seek.timestamp);
+        let drifted = match predicted {
+            Some(predicted) => (found_seek.seek_offset - predicted).abs() > self.epsilon_secs,
+            None => true,
+        };
+        let paused_changed = self
+            .last_accepted
+            .as_ref()
+            .is_some_and(|last| last.paused != found_seek.paused);
+
+        if !drifted && !paused_changed {
+            return None;
+        }
+
+        self.last_accepted = Some(AcceptedSeek {
+            seek_offset: found_seek.seek_offset,
+            timestamp: found_seek.timestamp,
+            paused: found_seek.paused,
+        });
+
+        Some(SeekCorrection {
+            position_secs: found_seek.seek_offset,
+            timestamp: found_seek.timestamp,
+            paused: found_seek.paused,
+        })
+    }
+
+    /// The predicted playback position at `at`, extrapolating from the last accepted seek.
+    /// Frozen at the last accepted offset if that seek indicated the stream is paused.
+    fn predicted_position_at(&self, at: DateTime<Local>) -> Option<f64> {
+        let last = self.last_accepted.as_ref()?;
+        if last.paused {
+            return Some(last.seek_offset);
+        }
+        let elapsed = at.signed_duration_since(last.timestamp).to_std().ok()?;
+        Some(last.seek_offset + elapsed.as_secs_f64())
+    }
+
+    /// The predicted playback position right now.
+    pub(crate) fn predicted_position_now(&self) -> Option<f64> {
+        self.predicted_position_at(Local::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::vrc_log_reader::parse_timestamp;
+
+    fn seek_at(base: DateTime<Local>, offset_secs: i64, seek_offset: f64, paused: bool) -> FoundSeek {
+        FoundSeek {
+            timestamp: base + Duration::seconds(offset_secs),
+            seek_offset,
+            paused,
+        }
+    }
+
+    #[test]
+    fn first_observation_is_always_accepted() {
+        let base = parse_timestamp("2024.04.22 17:55:53");
+        let mut tracker = SeekTracker::new(1.0);
+        let correction = tracker
+            .observe(&seek_at(base, 0, 10.0, false))
+            .expect("first observation should always be a correction");
+        assert_eq!(correction.position_secs, 10.0);
+        assert!(!correction.paused);
+    }
+
+    #[test]
+    fn small_drift_within_epsilon_is_suppressed() {
+        let base = parse_timestamp("2024.04.22 17:55:53");
+        let mut tracker = SeekTracker::new(1.0);
+        tracker.observe(&seek_at(base, 0, 10.0, false));
+
+        // 5 seconds later, ProTV reports 15.3s: only 0.3s off our 15.0s prediction.
+        let correction = tracker.observe(&seek_at(base, 5, 15.3, false));
+        assert!(correction.is_none());
+    }
+
+    #[test]
+    fn drift_beyond_epsilon_is_accepted() {
+        let base = parse_timestamp("2024.04.22 17:55:53");
+        let mut tracker = SeekTracker::new(1.0);
+        tracker.observe(&seek_at(base, 0, 10.0, false));
+
+        // 5 seconds later, ProTV reports 20.0s: 5s off our 15.0s prediction.
+        let correction = tracker
+            .observe(&seek_at(base, 5, 20.0, false))
+            .expect("drift beyond epsilon should be accepted");
+        assert_eq!(correction.position_secs, 20.0);
+    }
+
+    #[test]
+    fn out_of_order_events_are_ignored() {
+        let base = parse_timestamp("2024.04.22 17:55:53");
+        let mut tracker = SeekTracker::new(1.0);
+        tracker.observe(&seek_at(base, 10, 10.0, false));
+
+        // this event's timestamp is before the last accepted one.
+        let correction = tracker.observe(&seek_at(base, 5, 999.0, false));
+        assert!(correction.is_none());
+        assert_eq!(tracker.predicted_position_at(base + Duration::seconds(10)), Some(10.0));
+    }
+
+    #[test]
+    fn pause_state_change_is_accepted_even_without_drift() {
+        let base = parse_timestamp("2024.04.22 17:55:53");
+        let mut tracker = SeekTracker::new(1.0);
+        tracker.observe(&seek_at(base, 0, 10.0, false));
+
+        // same position as predicted, but now paused: should still be accepted.
+        let correction = tracker
+            .observe(&seek_at(base, 5, 15.0, true))
+            .expect("a pause/play transition should be accepted regardless of drift");
+        assert!(correction.paused);
+    }
+
+    #[test]
+    fn predicted_position_freezes_while_paused() {
+        let base = parse_timestamp("2024.04.22 17:55:53");
+        let mut tracker = SeekTracker::new(1.0);
+        tracker.observe(&seek_at(base, 0, 42.0, true));
+
+        // even long after the pause, the predicted position should not have advanced.
+        assert_eq!(
+            tracker.predicted_position_at(base + Duration::seconds(100)),
+            Some(42.0)
+        );
+    }
+}
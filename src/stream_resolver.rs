@@ -0,0 +1,114 @@
+use std::{
+    io::{Error, ErrorKind, Result},
+    process::Command,
+};
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct FfprobeOutput {
+    format: FfprobeFormat,
+    streams: Vec<FfprobeStream>,
+}
+
+#[derive(Deserialize)]
+struct FfprobeFormat {
+    duration: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FfprobeStream {}
+
+/// What `ffprobe` told us about a resolved stream URL.
+pub(crate) struct ResolvedStream {
+    pub(crate) duration_secs: Option<f64>,
+    pub(crate) track_count: usize,
+}
+
+/// How long to let `ffprobe` sit on an unresponsive stream (dead HLS origin, hung redirect)
+/// before giving up, in microseconds (the unit `ffprobe`'s `-timeout` option expects).
+const FFPROBE_TIMEOUT_MICROS: &str = "5000000";
+
+/// Shells out to `ffprobe` to resolve and validate a stream URL (following the
+/// `index.m3u8` -> `stream.m3u8` redirects VRChat's own log lines mention) and reports its
+/// duration and track layout.
+pub(crate) fn resolve_stream(url: &str) -> Result<ResolvedStream> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-print_format",
+            "json",
+            "-show_format",
+            "-show_streams",
+            "-timeout",
+            FFPROBE_TIMEOUT_MICROS,
+            url,
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!(
+                "ffprobe exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        ));
+    }
+
+    parse_ffprobe_output(&output.stdout)
+}
+
+/// The JSON-parsing half of `resolve_stream`, split out so it can be tested without actually
+/// shelling out to `ffprobe`.
+fn parse_ffprobe_output(json: &[u8]) -> Result<ResolvedStream> {
+    let parsed: FfprobeOutput =
+        serde_json::from_slice(json).map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+
+    Ok(ResolvedStream {
+        duration_secs: parsed.format.duration.and_then(|duration| duration.parse().ok()),
+        track_count: parsed.streams.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_duration_and_track_count_from_ffprobe_json() {
+        let json = br#"{
+            "streams": [{}, {}],
+            "format": { "duration": "123.456000" }
+        }"#;
+        let resolved = parse_ffprobe_output(json).expect("should parse");
+        assert_eq!(resolved.duration_secs, Some(123.456));
+        assert_eq!(resolved.track_count, 2);
+    }
+
+    #[test]
+    fn missing_duration_is_none_rather_than_an_error() {
+        let json = br#"{ "streams": [], "format": {} }"#;
+        let resolved = parse_ffprobe_output(json).expect("should parse");
+        assert_eq!(resolved.duration_secs, None);
+        assert_eq!(resolved.track_count, 0);
+    }
+
+    #[test]
+    fn unparseable_duration_is_none_rather_than_an_error() {
+        let json = br#"{ "streams": [{}], "format": { "duration": "not a number" } }"#;
+        let resolved = parse_ffprobe_output(json).expect("should parse");
+        assert_eq!(resolved.duration_secs, None);
+        assert_eq!(resolved.track_count, 1);
+    }
+
+    #[test]
+    fn malformed_json_is_an_error() {
+        match parse_ffprobe_output(b"not json") {
+            Err(err) => assert_eq!(err.kind(), ErrorKind::InvalidData),
+            Ok(_) => panic!("malformed JSON should not parse"),
+        }
+    }
+}
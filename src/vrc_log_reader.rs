@@ -1,36 +1,22 @@
-use lazy_regex::*;
 use std::{
+    cell::RefCell,
     fs::{self, File},
-    io::{BufRead, BufReader, Result},
+    io::{BufRead, BufReader, Result, Seek, SeekFrom},
     path::{Path, PathBuf},
     sync::mpsc,
 };
 
 use chrono::{DateTime, Local, TimeZone};
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
 
-use crate::{log_debug, log_error};
-
-fn try_match_seek_line(line: &String) -> Option<FoundSeek> {
-    if let Some(captures) = &SEEK_REGEX.captures(&line) {
-        let timestamp = captures.get(1).unwrap().as_str();
-        let seek_offset = captures.get(4).unwrap().as_str();
-
-        let timestamp = parse_timestamp(timestamp);
-        // also, parse the seek offset as a floating point
-        let seek_offset = seek_offset
-            .parse::<f64>()
-            .expect("Failed to parse seek offset as f64");
-        return Some(FoundSeek {
-            timestamp,
-            seek_offset,
-        });
-    }
-
-    None
-}
+use crate::{
+    detection_patterns::PatternRegistry,
+    log_debug, log_error,
+    vrc_log_store::{PersistedState, VrcLogStore},
+};
 
-fn parse_timestamp(timestamp: &str) -> DateTime<Local> {
+pub(crate) fn parse_timestamp(timestamp: &str) -> DateTime<Local> {
     // timestamp is of the form:
     // 2024.04.22 17:55:53
     // parse it as local time:
@@ -43,27 +29,11 @@ fn parse_timestamp(timestamp: &str) -> DateTime<Local> {
     timestamp
 }
 
-fn try_match_url_line(line: &String, line_number: u64) -> Option<FoundUrl> {
-    if let Some(captures) = &URL_REGEX.captures(&line) {
-        let timestamp = captures.get(1).unwrap().as_str();
-        let url = captures.get(2).unwrap().as_str();
-        let timestamp = parse_timestamp(timestamp);
-        return Some(FoundUrl {
-            timestamp,
-            url: url.to_string(),
-            found_url_on_line: line_number,
-        });
-    }
-
-    None
-}
-
-fn get_latest_vrc_log_file() -> Option<PathBuf> {
-    let log_dir = get_vrc_log_file_dir();
+fn get_latest_vrc_log_file(log_dir: &Path) -> Option<PathBuf> {
     let mut latest_log = None;
 
     // read dir
-    if let Ok(entries) = fs::read_dir(&log_dir) {
+    if let Ok(entries) = fs::read_dir(log_dir) {
         for entry in entries {
             if let Ok(entry) = entry {
                 if let Some(file_name) = entry.file_name().to_str() {
@@ -85,83 +55,136 @@ fn get_latest_vrc_log_file() -> Option<PathBuf> {
     latest_log
 }
 
-fn get_vrc_log_file_dir() -> String {
-    let log_dir = format!("{}/.steam/steam/steamapps/compatdata/438100/pfx/drive_c/users/steamuser/AppData/LocalLow/VRChat/VRChat", std::env::var("HOME").unwrap_or_default());
-    log_dir
+// Detection patterns (which log line shapes mean "found a URL" or "found a seek") live in
+// `detection_patterns::PatternRegistry` now, including the rationale for which ones are
+// enabled by default.
+
+/// Advances `file` past the first `n` lines, returning the byte offset right after the end
+/// of line `n`. Used at startup to translate the line count the caller already knows about
+/// (from the initial full-file scan) into a byte offset we can seek to directly, and by
+/// `get_latest_url_and_seek` to check that a cached line count still exists in the file.
+fn skip_to_line(file: &mut File, n: u64) -> Result<u64> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new(file);
+    let mut offset: u64 = 0;
+    for i in 0..n {
+        let mut buf = String::new();
+        let bytes_read = reader.read_line(&mut buf)? as u64;
+        if bytes_read == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("File is smaller than the given line number. {} < {}", i, n),
+            ));
+        }
+        offset += bytes_read;
+    }
+    Ok(offset)
 }
 
-/*
-We have several examples of log lines to choose from.
-
-"2024.04.14 23:28:20 Log        -  [AT INFO        TVManager (Theatre 1)] [AVPro1080p_Theatre1] loading URL:
-https://example.net/Media/Movies/spykids3d.mp4"
-Initially, I had this one, but obviously this doesn't work if I'm not in the theatre.
-
-"2024.04.14 21:25:36 Log        -  [AVProVideo] Opening http://example.com/mystream/stream.m3u8 (offset 0)
-with API MediaFoundation"
-AVProVideo might be a good option, but it likely doesn't capture usage of the Unity player, which iirc also needs
-fixing.
-
-"2024.04.14 21:25:36 Log        -  [Video Playback] URL 'http://example.com/mystream/index.m3u8' resolved to
-'http://example.com/mystream/stream.m3u8'"
-This one is more general, and might work everywhere, but it is 2 seconds delayed. If I don't need the resolution,
-I'd prefer the earlier the better.
-
-"2024.04.14 21:25:34 Log        -  [Video Playback] Attempting to resolve URL
-'http://example.com/mystream/index.m3u8'"
-I'll go with this one for now, as it's the earliest and the easiest.
-*/
-pub(crate) static URL_REGEX: Lazy<Regex> = lazy_regex!(
-    r"^([0-9.: ]+) Log +- +\[Video Playback\] Attempting to resolve URL '(https?://\S+)'"
-);
-
-// this is specifically for ProTV.
-// 2024.04.22 17:55:53 Log        -  [AT INFO    	TVManager (Theatre 1 TVManager)] Sync enforcement. Updating to 116.47
-// 2024.05.09 19:11:19 Log        -  [AT DEBUG 	TVManager (Theatre 1 TVManager)] Paused drift threshold exceeded. Updating to 64.8041
-pub(crate) static SEEK_REGEX: Lazy<Regex> = lazy_regex!(
-    r"^([0-9.: ]+) Log +- +\[AT (INFO|DEBUG)[ \t]+TVManager \(.*\)\] (Sync enforcement|Paused drift threshold exceeded). Updating to ([0-9.]+)$"
-);
+/// Reads whatever complete lines exist at or after `start_offset`, calling `callback` for
+/// each and incrementing `line_num` as we go. A trailing line with no terminating newline is
+/// left unread (and not counted into the returned offset) since VRChat may still be in the
+/// middle of writing it; we'll pick it up whole on the next event.
+fn read_appended_lines<FCallback>(
+    file: &mut File,
+    start_offset: u64,
+    line_num: &mut u64,
+    callback: &mut FCallback,
+) -> Result<u64>
+where
+    FCallback: FnMut(String, u64),
+{
+    file.seek(SeekFrom::Start(start_offset))?;
+    let mut reader = BufReader::new(file);
+    let mut offset = start_offset;
+    loop {
+        let mut buf = String::new();
+        let bytes_read = reader.read_line(&mut buf)? as u64;
+        if bytes_read == 0 || !buf.ends_with('\n') {
+            break;
+        }
+        offset += bytes_read;
+        *line_num += 1;
+        callback(buf.trim_end_matches(['\r', '\n']).to_string(), *line_num);
+    }
+    Ok(offset)
+}
+
+/// Like `read_appended_lines`, but also hands the callback the path it's currently reading
+/// from, since `tail_file` may have rotated `file` to a new path since the caller last heard
+/// from us.
+fn read_appended_lines_at<FCallback>(
+    file: &mut File,
+    current_path: &Path,
+    start_offset: u64,
+    line_num: &mut u64,
+    callback: &mut FCallback,
+) -> Result<u64>
+where
+    FCallback: FnMut(String, u64, &Path),
+{
+    read_appended_lines(file, start_offset, line_num, &mut |line, line_number| {
+        callback(line, line_number, current_path)
+    })
+}
 
 fn tail_file<FCallback>(
-    path: &PathBuf,
+    log_dir: &Path,
+    initial_path: &Path,
     start_after_line: u64,
     mut callback: FCallback,
 ) -> notify::Result<()>
 where
-    FCallback: FnMut(String, u64),
+    FCallback: FnMut(String, u64, &Path),
 {
-    let (tx, rx) = mpsc::channel();
-    let mut watcher = RecommendedWatcher::new(tx, Config::default())?;
-    watcher.watch(&path, RecursiveMode::NonRecursive)?;
-
-    let f = File::open(path)?;
-    // skip ahead initially
-    skip_n_lines(&f, start_after_line)?;
+    let mut current_path = initial_path.to_path_buf();
+    let mut file = File::open(&current_path)?;
     // line numbers are 1-based. if I skip 3 lines, I am now at line 4.
+    let mut offset = skip_to_line(&mut file, start_after_line)?;
+    let mut line_num = start_after_line;
 
-    // view the file as lines, and keep track of the line number
-    let lines = BufReader::new(&f).lines().enumerate();
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = RecommendedWatcher::new(tx, Config::default())?;
+    // Watch the whole log directory, not just the current file: once VRChat rotates to a new
+    // `output_log_*.txt`, nothing ever writes to the old file again, so a watch scoped to it
+    // would never see another event. Watching the directory catches the new file's own
+    // create/write events too.
+    watcher.watch(log_dir, RecursiveMode::NonRecursive)?;
 
-    // read the rest of the file as it exists, calling the callback for each line
-    for (i, line) in lines {
-        let current_line_num = (i as u64) + start_after_line;
-        let line = line.unwrap();
-        callback(line, current_line_num);
-    }
+    // read whatever's already been appended since start_after_line was captured.
+    offset = read_appended_lines_at(&mut file, &current_path, offset, &mut line_num, &mut callback)?;
 
-    // now, we'll keep watching the file for changes
     for res in rx {
         match res {
             Ok(_) => {
-                // log_debug!("File changed with event: {:?}", event);
-
-                // create a new BufReader for the file, because we're in the watcher loop so we can't move the old one
-                let lines = BufReader::new(&f).lines().enumerate();
-                for (current_line_num, line) in lines {
-                    let current_line_num = current_line_num.try_into().unwrap();
-                    let line = line.unwrap();
-                    callback(line, current_line_num);
+                if let Some(latest_path) = get_latest_vrc_log_file(log_dir) {
+                    if latest_path != current_path {
+                        log_debug!(
+                            "VRChat rotated to a new log file: {:?} -> {:?}",
+                            current_path,
+                            latest_path
+                        );
+                        current_path = latest_path;
+                        file = File::open(&current_path)?;
+                        offset = 0;
+                        line_num = 0;
+                    }
                 }
+
+                let current_len = file.metadata()?.len();
+                if current_len < offset {
+                    log_debug!(
+                        "Log file {:?} shrank from {} to {} bytes; VRChat must have started a new session.",
+                        current_path,
+                        offset,
+                        current_len
+                    );
+                    offset = 0;
+                    line_num = 0;
+                }
+
+                offset =
+                    read_appended_lines_at(&mut file, &current_path, offset, &mut line_num, &mut callback)?;
             }
             Err(err) => {
                 log_error!("Error: {:?}", err);
@@ -173,68 +196,132 @@ where
     Ok(())
 }
 
-fn skip_n_lines(file: &File, n: u64) -> Result<()> {
-    let mut lines = BufReader::new(file).lines();
-    for i in 0..n {
-        let line = lines.next();
-        if line.is_none() {
-            panic!("File is smaller than the given line number. {} < {}", i, n);
-        }
-    }
-    Ok(())
-}
-
 fn watch_file<FFoundUrl, FFoundSeek>(
-    log_path: &PathBuf,
+    log_dir: &Path,
+    log_path: &Path,
     start_after_line: u64,
+    patterns: &PatternRegistry,
     mut on_found_url: FFoundUrl,
     mut on_found_seek: FFoundSeek,
 ) where
-    FFoundUrl: FnMut(FoundUrl),
-    FFoundSeek: FnMut(FoundSeek),
+    FFoundUrl: FnMut(FoundUrl, u64, &Path),
+    FFoundSeek: FnMut(FoundSeek, u64, &Path),
 {
-    tail_file(log_path, start_after_line, |line, line_number| {
-        if let Some(found_url) = try_match_url_line(&line, line_number) {
-            on_found_url(found_url);
-        }
-        if let Some(found_seek) = try_match_seek_line(&line) {
-            on_found_seek(found_seek);
-        }
-    })
+    tail_file(
+        log_dir,
+        log_path,
+        start_after_line,
+        |line, line_number, current_path| {
+            if let Some(found_url) = patterns.try_match_url_line(&line, line_number) {
+                on_found_url(found_url, line_number, current_path);
+            }
+            if let Some(found_seek) = patterns.try_match_seek_line(&line) {
+                on_found_seek(found_seek, line_number, current_path);
+            }
+        },
+    )
     .expect("Failed to tail file.");
 }
 
 pub(crate) struct VrcLogReader {
     log_path: PathBuf,
     lines_read_initially: Option<u64>,
+    store: VrcLogStore,
+    patterns: PatternRegistry,
 }
 
 impl VrcLogReader {
     pub(crate) fn new(path: PathBuf) -> Self {
+        let store = VrcLogStore::new();
+        let patterns = store.load_pattern_registry();
         Self {
             log_path: path,
             lines_read_initially: None,
+            store,
+            patterns,
         }
     }
 
     pub(crate) fn from_latest() -> Self {
-        let log_path = get_latest_vrc_log_file().expect("No VRC log files found.");
-        Self::new(log_path)
+        let store = VrcLogStore::new();
+        let log_path =
+            get_latest_vrc_log_file(&store.log_dir()).expect("No VRC log files found.");
+        let patterns = store.load_pattern_registry();
+        Self {
+            log_path,
+            lines_read_initially: None,
+            store,
+            patterns,
+        }
     }
 
     pub(crate) fn get_latest_url_and_seek(&mut self) -> UrlAndSeekResult {
-        if let Some(found_url) = self.find_last_url() {
+        // if we already have cached state for this exact log file, trust it instead of
+        // rescanning a multi-gigabyte log from line 0. But the file may have been truncated or
+        // replaced under the same name (e.g. VRChat started a new session) while we weren't
+        // watching it, in which case the cached line count no longer exists in the file and we
+        // must fall back to a full rescan instead of handing out a line number `skip_to_line`
+        // would panic on.
+        if let Some(cached) = self.store.load_state() {
+            if cached.log_path == self.log_path {
+                let mut file = File::open(&self.log_path).expect("Expected log file to exist.");
+                if skip_to_line(&mut file, cached.lines_read).is_ok() {
+                    log_debug!(
+                        "Restoring cached state for {:?} at line {}",
+                        self.log_path,
+                        cached.lines_read
+                    );
+                    self.lines_read_initially = Some(cached.lines_read);
+                    return match (cached.found_url, cached.found_seek) {
+                        (Some(url), Some(seek)) => {
+                            UrlAndSeekResult::UrlAndSeek(url, seek, cached.lines_read)
+                        }
+                        (Some(url), None) => UrlAndSeekResult::Url(url, cached.lines_read),
+                        (None, Some(seek)) => UrlAndSeekResult::Seek(seek, cached.lines_read),
+                        (None, None) => UrlAndSeekResult::Nothing(cached.lines_read),
+                    };
+                }
+                log_debug!(
+                    "Cached line count {} is beyond the end of {:?}; the file must have been \
+                     truncated or replaced. Falling back to a full rescan.",
+                    cached.lines_read,
+                    self.log_path
+                );
+            }
+        }
+
+        let result = if let Some(found_url) = self.find_last_url() {
             if let Some(found_seek) = self.find_last_seek(found_url.found_url_on_line) {
-                return UrlAndSeekResult::UrlAndSeek(
+                UrlAndSeekResult::UrlAndSeek(
                     found_url,
                     found_seek,
                     self.lines_read_initially.unwrap(),
-                );
+                )
+            } else {
+                UrlAndSeekResult::Url(found_url, self.lines_read_initially.unwrap())
             }
-            return UrlAndSeekResult::Url(found_url, self.lines_read_initially.unwrap());
-        }
+        } else {
+            UrlAndSeekResult::Nothing(self.lines_read_initially.unwrap())
+        };
+        self.persist(&result);
+        result
+    }
 
-        UrlAndSeekResult::Nothing(self.lines_read_initially.unwrap())
+    fn persist(&self, result: &UrlAndSeekResult) {
+        let (found_url, found_seek, lines_read) = match result {
+            UrlAndSeekResult::Nothing(lines_read) => (None, None, *lines_read),
+            UrlAndSeekResult::Url(url, lines_read) => (Some(url.clone()), None, *lines_read),
+            UrlAndSeekResult::Seek(seek, lines_read) => (None, Some(seek.clone()), *lines_read),
+            UrlAndSeekResult::UrlAndSeek(url, seek, lines_read) => {
+                (Some(url.clone()), Some(seek.clone()), *lines_read)
+            }
+        };
+        self.store.save_state(&PersistedState {
+            log_path: self.log_path.clone(),
+            lines_read,
+            found_url,
+            found_seek,
+        });
     }
 
     fn find_last_url(&mut self) -> Option<FoundUrl> {
@@ -255,7 +342,7 @@ impl VrcLogReader {
                     log_debug!("Processed {} lines.", line_count);
                 }
 
-                if let Some(found_url) = try_match_url_line(&line, line_count) {
+                if let Some(found_url) = self.patterns.try_match_url_line(&line, line_count) {
                     last_url = Some(found_url);
                 }
             }
@@ -277,7 +364,7 @@ impl VrcLogReader {
                     continue;
                 }
 
-                if let Some(found_seek) = try_match_seek_line(&line) {
+                if let Some(found_seek) = self.patterns.try_match_seek_line(&line) {
                     last_seek = Some(found_seek);
                 }
             }
@@ -289,53 +376,228 @@ impl VrcLogReader {
 pub(crate) enum UrlAndSeekResult {
     Nothing(u64),
     Url(FoundUrl, u64),
+    /// A cached seek restored with no accompanying URL, because the URL line fell before
+    /// `start_after_line` in the watch session that produced it.
+    Seek(FoundSeek, u64),
     UrlAndSeek(FoundUrl, FoundSeek, u64),
 }
 
 pub(crate) struct VrcLogWatcher {
+    log_dir: PathBuf,
     log_path: PathBuf,
+    store: VrcLogStore,
+    patterns: PatternRegistry,
 }
 
 impl VrcLogWatcher {
-    fn new(path: PathBuf) -> Self {
-        Self { log_path: path }
+    fn new(log_dir: PathBuf, path: PathBuf) -> Self {
+        let store = VrcLogStore::new();
+        let patterns = store.load_pattern_registry();
+        Self {
+            log_dir,
+            log_path: path,
+            store,
+            patterns,
+        }
     }
 
     pub(crate) fn from_latest() -> Self {
-        let log_path = get_latest_vrc_log_file().expect("No VRC log files found.");
-        Self::new(log_path)
+        let store = VrcLogStore::new();
+        let log_dir = store.log_dir();
+        let log_path = get_latest_vrc_log_file(&log_dir).expect("No VRC log files found.");
+        let patterns = store.load_pattern_registry();
+        Self {
+            log_dir,
+            log_path,
+            store,
+            patterns,
+        }
     }
 
     pub(crate) fn watch_file<FFoundUrl, FFoundSeek>(
         &mut self,
         start_after_line: u64,
-        on_found_url: FFoundUrl,
-        on_found_seek: FFoundSeek,
+        mut on_found_url: FFoundUrl,
+        mut on_found_seek: FFoundSeek,
     ) where
         FFoundUrl: FnMut(FoundUrl),
         FFoundSeek: FnMut(FoundSeek),
     {
+        let log_path = self.log_path.clone();
+        let store = &self.store;
+        // seed from whatever the reader already established for this file, so the first
+        // save_state we write here doesn't clobber a real cached url/seek with None just
+        // because this watch session hasn't seen one yet.
+        let cached = store.load_state().filter(|cached| cached.log_path == log_path);
+        let last_found_url = RefCell::new(cached.as_ref().and_then(|cached| cached.found_url.clone()));
+        let last_found_seek = RefCell::new(cached.as_ref().and_then(|cached| cached.found_seek.clone()));
         watch_file(
-            &self.log_path,
+            &self.log_dir,
+            &log_path,
             start_after_line,
-            on_found_url,
-            on_found_seek,
+            &self.patterns,
+            |found_url: FoundUrl, current_line, current_path: &Path| {
+                *last_found_url.borrow_mut() = Some(found_url.clone());
+                store.save_state(&PersistedState {
+                    log_path: current_path.to_path_buf(),
+                    lines_read: current_line,
+                    found_url: last_found_url.borrow().clone(),
+                    found_seek: last_found_seek.borrow().clone(),
+                });
+                on_found_url(found_url);
+            },
+            |found_seek: FoundSeek, current_line, current_path: &Path| {
+                *last_found_seek.borrow_mut() = Some(found_seek.clone());
+                store.save_state(&PersistedState {
+                    log_path: current_path.to_path_buf(),
+                    lines_read: current_line,
+                    found_url: last_found_url.borrow().clone(),
+                    found_seek: last_found_seek.borrow().clone(),
+                });
+                on_found_seek(found_seek);
+            },
         );
     }
 }
 
+pub(crate) enum VrcLogWatcherEvent {
+    FoundUrl(FoundUrl),
+    FoundSeek(FoundSeek),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub(crate) struct FoundSeek {
     pub(crate) timestamp: DateTime<Local>,
     pub(crate) seek_offset: f64,
+    /// Whether this event was a `Paused drift threshold exceeded` line, meaning playback is
+    /// paused rather than actively drifting.
+    pub(crate) paused: bool,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub(crate) struct FoundUrl {
     pub(crate) timestamp: DateTime<Local>,
     pub(crate) url: String,
-    found_url_on_line: u64,
+    pub(crate) found_url_on_line: u64,
 }
 
-pub(crate) enum VrcLogWatcherEvent {
-    FoundUrl(FoundUrl),
-    FoundSeek(FoundSeek),
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    /// A scratch file under the system temp dir, unique to this process and test name so
+    /// parallel test runs don't collide. Removed when dropped.
+    struct ScratchFile {
+        path: PathBuf,
+    }
+
+    impl ScratchFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "vrc_avpro_sucks_test_{}_{}_{}",
+                std::process::id(),
+                name,
+                contents.len()
+            ));
+            fs::write(&path, contents).expect("failed to write scratch file");
+            Self { path }
+        }
+
+        fn open(&self) -> File {
+            File::open(&self.path).expect("failed to open scratch file")
+        }
+    }
+
+    impl Drop for ScratchFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    #[test]
+    fn skip_to_line_returns_offset_after_nth_line() {
+        let scratch = ScratchFile::new("skip_basic", "one\ntwo\nthree\n");
+        let mut file = scratch.open();
+        assert_eq!(skip_to_line(&mut file, 0).unwrap(), 0);
+        assert_eq!(skip_to_line(&mut file, 1).unwrap(), 4); // "one\n"
+        assert_eq!(skip_to_line(&mut file, 2).unwrap(), 8); // "one\ntwo\n"
+    }
+
+    #[test]
+    fn skip_to_line_errors_when_file_is_smaller_than_requested() {
+        let scratch = ScratchFile::new("skip_truncated", "only one line\n");
+        let mut file = scratch.open();
+        assert!(skip_to_line(&mut file, 5).is_err());
+    }
+
+    #[test]
+    fn read_appended_lines_stops_at_an_unterminated_trailing_line() {
+        let scratch = ScratchFile::new("appended_partial", "complete line\npartial line without newline");
+        let mut file = scratch.open();
+        let mut line_num = 0;
+        let mut seen = Vec::new();
+        let offset = read_appended_lines(&mut file, 0, &mut line_num, &mut |line, n| {
+            seen.push((line, n));
+        })
+        .unwrap();
+
+        assert_eq!(seen, vec![("complete line".to_string(), 1)]);
+        assert_eq!(line_num, 1);
+        assert_eq!(offset, "complete line\n".len() as u64);
+    }
+
+    #[test]
+    fn read_appended_lines_picks_up_a_completed_trailing_line_on_the_next_call() {
+        let scratch = ScratchFile::new("appended_two_calls", "complete line\n");
+        let mut file = scratch.open();
+        let mut line_num = 0;
+        let mut seen = Vec::new();
+        let offset = read_appended_lines(&mut file, 0, &mut line_num, &mut |line, n| {
+            seen.push((line, n));
+        })
+        .unwrap();
+        assert_eq!(seen.len(), 1);
+
+        // simulate VRChat finishing the line it was mid-write on.
+        {
+            let mut appender = std::fs::OpenOptions::new()
+                .append(true)
+                .open(&scratch.path)
+                .unwrap();
+            writeln!(appender, "second line").unwrap();
+        }
+
+        let mut file = scratch.open();
+        read_appended_lines(&mut file, offset, &mut line_num, &mut |line, n| {
+            seen.push((line, n));
+        })
+        .unwrap();
+
+        assert_eq!(
+            seen,
+            vec![
+                ("complete line".to_string(), 1),
+                ("second line".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn get_latest_vrc_log_file_picks_the_lexicographically_last_match() {
+        let dir = std::env::temp_dir().join(format!(
+            "vrc_avpro_sucks_test_latest_{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["output_log_2024-01-01.txt", "output_log_2024-06-01.txt", "not_a_log.txt"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        let latest = get_latest_vrc_log_file(&dir).expect("should find a log file");
+        assert_eq!(latest, dir.join("output_log_2024-06-01.txt"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
\ No newline at end of file
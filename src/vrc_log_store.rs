@@ -0,0 +1,363 @@
+use std::{fs, path::PathBuf};
+
+use directories::ProjectDirs;
+use lazy_regex::regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    detection_patterns::{DetectionPattern, PatternKind, PatternRegistry},
+    log_debug, log_error,
+    vrc_log_reader::{FoundSeek, FoundUrl},
+};
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("net", "libjared", "vrc-avpro-sucks")
+}
+
+fn config_file_path() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.config_dir().join("config.txt"))
+}
+
+fn cache_file_path() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.cache_dir().join("last_seen.json"))
+}
+
+/// Reads a user override for the VRChat log directory out of the config file, if present.
+///
+/// The config file is a plain `key=value` file (one setting per line, matching the rest of
+/// this crate's preference for hand-rolled parsing over pulling in a config format crate).
+/// Besides the `[pattern]` sections read by `read_pattern_overrides`, the only recognized key
+/// is `log_dir`.
+fn read_log_dir_override() -> Option<PathBuf> {
+    let path = config_file_path()?;
+    let contents = fs::read_to_string(&path).ok()?;
+    parse_log_dir_override(&contents)
+}
+
+/// The `log_dir=` line-parsing half of `read_log_dir_override`, split out so it can be tested
+/// without touching the real config file.
+fn parse_log_dir_override(contents: &str) -> Option<PathBuf> {
+    for line in contents.lines() {
+        let line = line.trim();
+        if let Some(value) = line.strip_prefix("log_dir=") {
+            let value = value.trim();
+            if !value.is_empty() {
+                return Some(PathBuf::from(value));
+            }
+        }
+    }
+    None
+}
+
+/// A `[pattern]` section from the config file, parsed field-by-field as we walk the lines.
+#[derive(Default)]
+struct PatternBuilder {
+    name: Option<String>,
+    kind: Option<PatternKind>,
+    regex: Option<String>,
+    timestamp_group: Option<usize>,
+    url_group: Option<usize>,
+    offset_group: Option<usize>,
+    paused_group: Option<usize>,
+}
+
+impl PatternBuilder {
+    fn build(self) -> Option<DetectionPattern> {
+        let name = self.name?;
+        let kind = self.kind?;
+        let regex = match Regex::new(&self.regex?) {
+            Ok(regex) => regex,
+            Err(err) => {
+                log_error!("Invalid regex for detection pattern {:?}: {:?}", name, err);
+                return None;
+            }
+        };
+        Some(DetectionPattern {
+            name,
+            kind,
+            regex,
+            timestamp_group: self.timestamp_group?,
+            url_group: self.url_group,
+            offset_group: self.offset_group,
+            paused_group: self.paused_group,
+        })
+    }
+}
+
+/// Reads the user-registered detection patterns out of the config file's `[pattern]` sections.
+///
+/// Each section looks like:
+/// ```text
+/// [pattern]
+/// name=my_unity_player_url
+/// kind=url
+/// regex=^([0-9.: ]+) .*MyPlayer.*url=(\S+)
+/// timestamp_group=1
+/// url_group=2
+/// ```
+/// Patterns are appended after the built-in defaults in file order, so the defaults always
+/// get first chance to match a line; custom patterns only kick in once those miss.
+fn read_pattern_overrides() -> Vec<DetectionPattern> {
+    let Some(path) = config_file_path() else {
+        return Vec::new();
+    };
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    parse_pattern_overrides(&contents)
+}
+
+/// The section-parsing half of `read_pattern_overrides`, split out so it can be tested without
+/// touching the real config file.
+fn parse_pattern_overrides(contents: &str) -> Vec<DetectionPattern> {
+    let mut patterns = Vec::new();
+    let mut current: Option<PatternBuilder> = None;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line == "[pattern]" {
+            if let Some(builder) = current.take() {
+                if let Some(pattern) = builder.build() {
+                    patterns.push(pattern);
+                }
+            }
+            current = Some(PatternBuilder::default());
+            continue;
+        }
+        let Some(builder) = current.as_mut() else {
+            continue;
+        };
+        if let Some(value) = line.strip_prefix("name=") {
+            builder.name = Some(value.to_string());
+        } else if let Some(value) = line.strip_prefix("kind=") {
+            builder.kind = match value {
+                "url" => Some(PatternKind::Url),
+                "seek" => Some(PatternKind::Seek),
+                _ => {
+                    log_error!("Unknown detection pattern kind: {:?}", value);
+                    None
+                }
+            };
+        } else if let Some(value) = line.strip_prefix("regex=") {
+            builder.regex = Some(value.to_string());
+        } else if let Some(value) = line.strip_prefix("timestamp_group=") {
+            builder.timestamp_group = value.parse().ok();
+        } else if let Some(value) = line.strip_prefix("url_group=") {
+            builder.url_group = value.parse().ok();
+        } else if let Some(value) = line.strip_prefix("offset_group=") {
+            builder.offset_group = value.parse().ok();
+        } else if let Some(value) = line.strip_prefix("paused_group=") {
+            builder.paused_group = value.parse().ok();
+        }
+    }
+    if let Some(builder) = current {
+        if let Some(pattern) = builder.build() {
+            patterns.push(pattern);
+        }
+    }
+
+    patterns
+}
+
+fn native_windows_log_dir() -> Option<PathBuf> {
+    let user_profile = std::env::var("USERPROFILE").ok()?;
+    Some(PathBuf::from(user_profile).join("AppData/LocalLow/VRChat/VRChat"))
+}
+
+fn proton_log_dir() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(
+        ".steam/steam/steamapps/compatdata/438100/pfx/drive_c/users/steamuser/AppData/LocalLow/VRChat/VRChat",
+    ))
+}
+
+/// Resolves the directory VRChat writes its `output_log_*.txt` files to.
+///
+/// Tries, in order: a user override from the config file, the native Windows install
+/// location, and the Proton compatdata location used on Linux/Steam Deck.
+pub(crate) fn get_vrc_log_file_dir() -> PathBuf {
+    if let Some(override_dir) = read_log_dir_override() {
+        log_debug!("Using log dir override from config: {:?}", override_dir);
+        return override_dir;
+    }
+
+    if let Some(native_dir) = native_windows_log_dir() {
+        if native_dir.is_dir() {
+            return native_dir;
+        }
+    }
+
+    if let Some(proton_dir) = proton_log_dir() {
+        if proton_dir.is_dir() {
+            return proton_dir;
+        }
+    }
+
+    // Fall back to whichever path we'd have picked, even if it doesn't exist yet, so callers
+    // get a sensible error message instead of an empty path.
+    native_windows_log_dir()
+        .or_else(proton_log_dir)
+        .unwrap_or_default()
+}
+
+/// The last `FoundUrl`/`FoundSeek` we saw, plus where we saw them, so a fresh reader doesn't
+/// have to rescan a multi-gigabyte log from line 0.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct PersistedState {
+    pub(crate) log_path: PathBuf,
+    pub(crate) lines_read: u64,
+    pub(crate) found_url: Option<FoundUrl>,
+    pub(crate) found_seek: Option<FoundSeek>,
+}
+
+/// Resolves the VRChat log directory (afresh on every `log_dir` call) and reads and writes the
+/// cached last-seen state.
+///
+/// `VrcLogReader` and `VrcLogWatcher` both go through this instead of touching the filesystem
+/// paths directly, so the caching behavior stays in one place.
+pub(crate) struct VrcLogStore;
+
+impl VrcLogStore {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+
+    pub(crate) fn log_dir(&self) -> PathBuf {
+        get_vrc_log_file_dir()
+    }
+
+    pub(crate) fn load_pattern_registry(&self) -> PatternRegistry {
+        PatternRegistry::defaults().with_extra_patterns(read_pattern_overrides())
+    }
+
+    pub(crate) fn load_state(&self) -> Option<PersistedState> {
+        let path = cache_file_path()?;
+        let contents = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(state) => Some(state),
+            Err(err) => {
+                log_error!("Failed to parse cached state at {:?}: {:?}", path, err);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn save_state(&self, state: &PersistedState) {
+        let Some(path) = cache_file_path() else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            if let Err(err) = fs::create_dir_all(parent) {
+                log_error!("Failed to create cache dir {:?}: {:?}", parent, err);
+                return;
+            }
+        }
+        match serde_json::to_string_pretty(state) {
+            Ok(json) => {
+                if let Err(err) = fs::write(&path, json) {
+                    log_error!("Failed to write cached state to {:?}: {:?}", path, err);
+                }
+            }
+            Err(err) => log_error!("Failed to serialize cached state: {:?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vrc_log_reader::parse_timestamp;
+
+    #[test]
+    fn log_dir_override_picks_up_the_key() {
+        let contents = "# a comment\nlog_dir=/custom/vrc/logs\n";
+        assert_eq!(
+            parse_log_dir_override(contents),
+            Some(PathBuf::from("/custom/vrc/logs"))
+        );
+    }
+
+    #[test]
+    fn log_dir_override_is_none_when_key_absent_or_blank() {
+        assert_eq!(parse_log_dir_override("some_other_key=value"), None);
+        assert_eq!(parse_log_dir_override("log_dir=   \n"), None);
+        assert_eq!(parse_log_dir_override(""), None);
+    }
+
+    #[test]
+    fn pattern_overrides_parses_a_full_section() {
+        let contents = "\
+[pattern]
+name=my_unity_player_url
+kind=url
+regex=^([0-9.: ]+) .*MyPlayer.*url=(\\S+)
+timestamp_group=1
+url_group=2
+";
+        let patterns = parse_pattern_overrides(contents);
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].name, "my_unity_player_url");
+        assert_eq!(patterns[0].kind, PatternKind::Url);
+        assert_eq!(patterns[0].timestamp_group, 1);
+        assert_eq!(patterns[0].url_group, Some(2));
+    }
+
+    #[test]
+    fn pattern_overrides_skips_a_section_missing_required_fields() {
+        // no regex= line, so the builder can never produce a DetectionPattern.
+        let contents = "\
+[pattern]
+name=incomplete
+kind=url
+timestamp_group=1
+";
+        assert!(parse_pattern_overrides(contents).is_empty());
+    }
+
+    #[test]
+    fn pattern_overrides_parses_multiple_sections() {
+        let contents = "\
+[pattern]
+name=first
+kind=url
+regex=^(\\S+)$
+timestamp_group=1
+url_group=1
+
+[pattern]
+name=second
+kind=seek
+regex=^(\\S+)$
+timestamp_group=1
+offset_group=1
+";
+        let patterns = parse_pattern_overrides(contents);
+        assert_eq!(patterns.len(), 2);
+        assert_eq!(patterns[0].name, "first");
+        assert_eq!(patterns[1].name, "second");
+        assert_eq!(patterns[1].kind, PatternKind::Seek);
+    }
+
+    #[test]
+    fn persisted_state_round_trips_through_json() {
+        let state = PersistedState {
+            log_path: PathBuf::from("/vrc/logs/output_log_2024.04.22.txt"),
+            lines_read: 1234,
+            found_url: None,
+            found_seek: Some(FoundSeek {
+                timestamp: parse_timestamp("2024.04.22 17:55:53"),
+                seek_offset: 42.0,
+                paused: true,
+            }),
+        };
+
+        let json = serde_json::to_string(&state).expect("should serialize");
+        let restored: PersistedState = serde_json::from_str(&json).expect("should deserialize");
+
+        assert_eq!(restored.log_path, state.log_path);
+        assert_eq!(restored.lines_read, state.lines_read);
+        assert!(restored.found_url.is_none());
+        let restored_seek = restored.found_seek.expect("seek should round-trip");
+        assert_eq!(restored_seek.seek_offset, 42.0);
+        assert!(restored_seek.paused);
+    }
+}